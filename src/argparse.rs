@@ -1,6 +1,8 @@
 use super::errors::*;
 use super::subprocess::SubprocessCommand;
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
+use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use pcre2::bytes::{Regex as Pcre2Regex, RegexBuilder as Pcre2RegexBuilder};
 use regex::{Regex, RegexBuilder};
 use std::{collections::HashMap, env, fs, path::PathBuf};
 use structopt::StructOpt;
@@ -28,10 +30,26 @@ pub struct Arguments {
   #[structopt(short, long)]
   pub exact: bool,
 
+  /// Use PCRE2 instead of the default regex engine, enables lookaround & backreferences
+  #[structopt(short = "P", long = "pcre2")]
+  pub pcre2: bool,
+
   /// Standard regex flags: ie. -f imx, full list: https://github.com/ms-jpq/sad
   #[structopt(short, long)]
   pub flags: Option<String>,
 
+  /// Force case insensitive matching, overrides smart-case and inline flags
+  #[structopt(long)]
+  pub ignore_case: bool,
+
+  /// Force case sensitive matching, overrides smart-case and inline flags
+  #[structopt(long)]
+  pub case_sensitive: bool,
+
+  /// Explicitly request smart-case matching (the default)
+  #[structopt(long)]
+  pub smart_case: bool,
+
   /// Colourizing program, disable = never, default = $GIT_PAGER
   #[structopt(short, long)]
   pub pager: Option<String>,
@@ -55,6 +73,40 @@ pub struct Arguments {
   /// *Internal use only*
   #[structopt(short = "c")]
   pub shell: Option<String>,
+
+  /// Walk the given root(s) instead of reading paths from stdin (positional roots are
+  /// not accepted, since `pattern`/`replace` already occupy the positional slots)
+  #[structopt(long, parse(from_os_str))]
+  pub walk: Vec<PathBuf>,
+
+  /// Include hidden files when walking
+  #[structopt(long)]
+  pub hidden: bool,
+
+  /// Disable .gitignore/.ignore filtering when walking
+  #[structopt(long)]
+  pub no_ignore: bool,
+
+  /// Only walk paths matching this glob, repeatable
+  #[structopt(long)]
+  pub glob: Vec<String>,
+
+  /// Only walk entries of this kind: f(ile) | d(irectory)
+  #[structopt(long = "type", possible_values = &["f", "d"])]
+  pub entry_type: Option<String>,
+
+  /// Additional find/replace expression as PAT<0x04>REPL, a literal 0x04 byte, not the
+  /// two characters `\x04` (bash: --expr $'PAT\x04REPL'), repeatable, applied in order
+  #[structopt(long)]
+  pub expr: Vec<String>,
+
+  /// File of additional find/replace expressions, one `pattern<TAB>replacement` per line
+  #[structopt(long, parse(from_os_str))]
+  pub script: Option<PathBuf>,
+
+  /// Colourize diff output without an external pager
+  #[structopt(long, default_value = "auto", possible_values = &["auto", "always", "never"])]
+  pub color: String,
 }
 
 impl Arguments {
@@ -75,15 +127,109 @@ impl Arguments {
         }
         Arguments::from_iter(args)
       }
+      (Some(name), _, _) if !p_is_internal(&args) => {
+        let real = args[1..].to_vec();
+        let defaults = p_strip_overridden(p_config_defaults(), &real);
+        let mut full = vec![name.to_owned()];
+        full.extend(defaults);
+        full.extend(real);
+        Arguments::from_iter(full)
+      }
       _ => Arguments::from_args(),
     }
   }
 }
 
+fn p_is_internal(args: &[String]) -> bool {
+  args
+    .iter()
+    .any(|arg| arg == "--internal-preview" || arg == "--internal-patch")
+}
+
+fn p_config_path() -> Option<PathBuf> {
+  env::var("XDG_CONFIG_HOME")
+    .ok()
+    .map(PathBuf::from)
+    .or_else(|| env::var("HOME").ok().map(|home| PathBuf::from(home).join(".config")))
+    .map(|base| base.join("sad").join("config"))
+}
+
+// clap 2 aborts on a repeated occurrence of a non-multiple option or flag rather than
+// letting the last one win, so any of these set on the real argv must drop the config
+// default entirely (plus its value, for the ones that take one), value or boolean alike.
+const OVERRIDABLE_OPTS: &[(&str, &str, bool)] = &[
+  ("-f", "--flags", true),
+  ("-p", "--pager", true),
+  ("", "--fzf", true),
+  ("-u", "--unified", true),
+  ("", "--script", true),
+  ("", "--type", true),
+  ("", "--color", true),
+  ("-0", "--read0", false),
+  ("-k", "--commit", false),
+  ("-e", "--exact", false),
+  ("-P", "--pcre2", false),
+  ("", "--ignore-case", false),
+  ("", "--case-sensitive", false),
+  ("", "--smart-case", false),
+  ("", "--hidden", false),
+  ("", "--no-ignore", false),
+];
+
+fn p_opt_name(token: &str) -> &str {
+  token.split('=').next().unwrap_or(token)
+}
+
+fn p_strip_overridden(defaults: Vec<String>, real_args: &[String]) -> Vec<String> {
+  let is_set = |short: &str, long: &str| {
+    real_args
+      .iter()
+      .any(|arg| (!short.is_empty() && p_opt_name(arg) == short) || p_opt_name(arg) == long)
+  };
+
+  let mut out = Vec::with_capacity(defaults.len());
+  let mut skip_value = false;
+  for token in defaults {
+    if skip_value {
+      skip_value = false;
+      continue;
+    }
+
+    let name = p_opt_name(&token);
+    if let Some((short, long, takes_value)) = OVERRIDABLE_OPTS.iter().find(|(s, l, _)| name == *s || name == *l) {
+      if is_set(short, long) {
+        if *takes_value && !token.contains('=') {
+          skip_value = true;
+        }
+        continue;
+      }
+    }
+    out.push(token);
+  }
+  out
+}
+
+fn p_config_defaults() -> Vec<String> {
+  let mut defaults = Vec::new();
+
+  if let Some(path) = p_config_path() {
+    if let Ok(contents) = fs::read_to_string(path) {
+      defaults.extend(shlex::split(contents.trim()).unwrap_or_default());
+    }
+  }
+
+  if let Ok(opts) = env::var("SAD_OPTIONS") {
+    defaults.extend(shlex::split(&opts).unwrap_or_default());
+  }
+
+  defaults
+}
+
 #[derive(Clone, Debug)]
 pub enum Engine {
   AhoCorasick(AhoCorasick, String),
   Regex(Regex, String),
+  Pcre2(Pcre2Regex, String),
 }
 
 #[derive(Clone, Debug)]
@@ -96,6 +242,7 @@ pub enum Action {
 #[derive(Clone, Debug)]
 pub enum Printer {
   Stdout,
+  Color,
   Pager(SubprocessCommand),
 }
 
@@ -103,10 +250,11 @@ pub enum Printer {
 pub struct Options {
   pub name: String,
   pub action: Action,
-  pub engine: Engine,
+  pub engine: Vec<Engine>,
   pub fzf: Option<Vec<String>>,
   pub printer: Printer,
   pub unified: usize,
+  pub paths: Option<Vec<PathBuf>>,
 }
 
 impl Options {
@@ -122,24 +270,36 @@ impl Options {
       .to_string_lossy()
       .to_string();
 
-    let mut flagset = p_auto_flags(&args.pattern);
-    flagset.extend(
-      args
-        .flags
-        .unwrap_or_default()
-        .split_terminator("")
-        .skip(1)
-        .map(String::from),
-    );
-
-    let engine = {
-      let replace = args.replace.unwrap_or_default();
-      if args.exact {
-        Engine::AhoCorasick(p_aho_corasick(&args.pattern, &flagset)?, replace)
-      } else {
-        Engine::Regex(p_regex(&args.pattern, &flagset)?, replace)
+    let paths = p_walk(&args)?;
+    let forced_case = p_forced_case(args.ignore_case, args.case_sensitive, args.smart_case)?;
+
+    let mut engine = vec![p_engine(
+      &args.pattern,
+      args.replace.unwrap_or_default(),
+      args.exact,
+      args.pcre2,
+      args.flags.as_deref().unwrap_or(""),
+      &forced_case,
+    )?];
+
+    for raw in &args.expr {
+      let mut parts = raw.splitn(3, '\x04');
+      let pattern = parts.next().unwrap_or_default();
+      let replace = parts.next().unwrap_or_default().to_owned();
+      let flags = parts.next().unwrap_or("");
+      engine.push(p_engine(pattern, replace, args.exact, args.pcre2, flags, &forced_case)?);
+    }
+
+    if let Some(script) = &args.script {
+      let contents = fs::read_to_string(script).into_sadness()?;
+      for line in contents.lines().filter(|line| !line.is_empty()) {
+        let mut parts = line.splitn(3, '\t');
+        let pattern = parts.next().unwrap_or_default();
+        let replace = parts.next().unwrap_or_default().to_owned();
+        let flags = parts.next().unwrap_or("");
+        engine.push(p_engine(pattern, replace, args.exact, args.pcre2, flags, &forced_case)?);
       }
-    };
+    }
 
     let fzf = p_fzf(args.fzf);
 
@@ -153,6 +313,7 @@ impl Options {
 
     let printer = match p_pager(args.pager) {
       Some(cmd) => Printer::Pager(cmd),
+      None if p_color(&args.color) => Printer::Color,
       None => Printer::Stdout,
     };
 
@@ -163,10 +324,51 @@ impl Options {
       fzf,
       printer,
       unified: args.unified.unwrap_or(3),
+      paths,
     })
   }
 }
 
+fn p_forced_case(ignore_case: bool, case_sensitive: bool, smart_case: bool) -> SadResult<Option<String>> {
+  match (ignore_case, case_sensitive, smart_case) {
+    (true, false, false) => Ok(Some("i".into())),
+    (false, true, false) => Ok(Some("I".into())),
+    (false, false, _) => Ok(None),
+    _ => Err(Failure::Simple(
+      "--ignore-case, --case-sensitive and --smart-case are mutually exclusive".into(),
+    )),
+  }
+}
+
+fn p_engine(
+  pattern: &str,
+  replace: String,
+  exact: bool,
+  pcre2: bool,
+  extra_flags: &str,
+  forced_case: &Option<String>,
+) -> SadResult<Engine> {
+  let mut flagset = match forced_case {
+    Some(case) => vec![case.clone()],
+    None => p_auto_flags(pattern),
+  };
+  flagset.extend(
+    extra_flags
+      .split_terminator("")
+      .skip(1)
+      .filter(|flag| forced_case.is_none() || (*flag != "i" && *flag != "I"))
+      .map(String::from),
+  );
+
+  if exact {
+    Ok(Engine::AhoCorasick(p_aho_corasick(pattern, &flagset)?, replace))
+  } else if pcre2 {
+    Ok(Engine::Pcre2(p_pcre2(pattern, &flagset)?, replace))
+  } else {
+    Ok(Engine::Regex(p_regex(pattern, &flagset)?, replace))
+  }
+}
+
 fn p_auto_flags(pattern: &str) -> Vec<String> {
   for c in pattern.chars() {
     if c.is_uppercase() {
@@ -204,6 +406,74 @@ fn p_regex(pattern: &str, flags: &[String]) -> SadResult<Regex> {
   re.build().into_sadness()
 }
 
+fn p_pcre2(pattern: &str, flags: &[String]) -> SadResult<Pcre2Regex> {
+  // pcre2::bytes::RegexBuilder has no swap_greed/ungreedy method, so "U" is applied via
+  // PCRE2's own (?U) inline modifier, which has the same effect as regex's swap_greed.
+  let ungreedy = flags.iter().any(|flag| flag == "U");
+  let pattern = if ungreedy {
+    format!("(?U){}", pattern)
+  } else {
+    pattern.to_owned()
+  };
+
+  let mut re = Pcre2RegexBuilder::new();
+  for flag in flags.iter().filter(|flag| flag.as_str() != "U") {
+    match flag.as_str() {
+      "I" => re.caseless(false),
+      "i" => re.caseless(true),
+      "m" => re.multi_line(true),
+      "s" => re.dotall(true),
+      "x" => re.extended(true),
+      _ => return Err(Failure::Simple("Invalid flags".into())),
+    };
+  }
+  re.build(&pattern).into_sadness()
+}
+
+fn p_walk(args: &Arguments) -> SadResult<Option<Vec<PathBuf>>> {
+  if args.walk.is_empty() {
+    return Ok(None);
+  }
+
+  let mut paths = Vec::new();
+  for root in &args.walk {
+    let mut overrides = OverrideBuilder::new(root);
+    for pattern in &args.glob {
+      overrides.add(pattern).into_sadness()?;
+    }
+    let overrides = overrides.build().into_sadness()?;
+
+    let mut builder = WalkBuilder::new(root);
+    builder
+      .hidden(!args.hidden)
+      .git_ignore(!args.no_ignore)
+      .git_global(!args.no_ignore)
+      .git_exclude(!args.no_ignore)
+      .ignore(!args.no_ignore)
+      .overrides(overrides);
+
+    for entry in builder.build() {
+      let entry = match entry {
+        Ok(entry) => entry,
+        Err(err) => {
+          eprintln!("sad: {}", err);
+          continue;
+        }
+      };
+      // entry_type is validated to be "f"/"d" at parse time; an entry whose file_type()
+      // can't be determined (None) simply never matches, rather than aborting the walk.
+      let is_match = match args.entry_type.as_deref() {
+        Some("d") => entry.file_type().map_or(false, |ft| ft.is_dir()),
+        _ => entry.file_type().map_or(false, |ft| ft.is_file()),
+      };
+      if is_match {
+        paths.push(entry.into_path());
+      }
+    }
+  }
+  Ok(Some(paths))
+}
+
 fn p_tty() -> bool {
   atty::is(atty::Stream::Stdout)
 }
@@ -219,6 +489,14 @@ fn p_fzf(fzf: Option<String>) -> Option<Vec<String>> {
   }
 }
 
+fn p_color(color: &str) -> bool {
+  match color {
+    "always" => true,
+    "never" => false,
+    _ => p_tty(),
+  }
+}
+
 fn p_pager(pager: Option<String>) -> Option<SubprocessCommand> {
   pager.or(env::var("GIT_PAGER").ok()).and_then(|val| {
     if val == "never" {